@@ -1,11 +1,28 @@
+use chacha20poly1305::aead::{Aead, NewAead};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
 use curve25519_dalek::{
     constants::RISTRETTO_BASEPOINT_POINT,
     ristretto::RistrettoPoint,
     scalar::Scalar,
 };
+use hkdf::Hkdf;
 use rand::rngs::OsRng;
 use rand::RngCore;
-use sha2::{Sha256, Digest};
+use sha2::{Sha256, Sha512};
+use std::fmt;
+use subtle::{Choice, ConditionallySelectable};
+
+mod ot_extension;
+pub use ot_extension::{OTExtensionSender, OTExtensionReceiver, OTExtensionKeys, K as OT_EXTENSION_K};
+
+mod ot_n;
+pub use ot_n::{OTSenderN, OTReceiverN};
+
+mod random_ot;
+pub use random_ot::{
+    CorrelatedOTSender, CorrelatedOtSenderPool, RandomOTReceiver, RandomOTSender, RandomOtReceiverPool,
+    RandomOtSenderPool,
+};
 
 //sender - Alice
 pub struct OTSender {
@@ -36,7 +53,42 @@ pub struct BobMessage {
 #[derive(Debug, Clone)]
 pub struct AliceMessage2 {
     pub encrypted_m0: Vec<u8>,
+    pub nonce0: [u8; 12],
     pub encrypted_m1: Vec<u8>,
+    pub nonce1: [u8; 12],
+}
+
+/// An AEAD ciphertext paired with the fresh nonce it was sealed under.
+/// Shared by the OT-extension, OT-N, and random-OT layers so each streaming
+/// encrypt/derandomize call doesn't need its own ad-hoc tuple type.
+pub(crate) type SealedMessage = (Vec<u8>, [u8; 12]);
+
+/// Error returned when an OT ciphertext fails to authenticate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OtError {
+    Decryption,
+}
+
+impl fmt::Display for OtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OtError::Decryption => write!(f, "failed to authenticate and decrypt OT ciphertext"),
+        }
+    }
+}
+
+impl std::error::Error for OtError {}
+
+/// Protocol version label, folded into every derived key so a future wire
+/// format change can never be confused with this one.
+const PROTOCOL_VERSION: &[u8] = b"ristretto-ot/v2";
+
+/// A second Ristretto generator with no known discrete-log relation to the
+/// basepoint. Bob's choice is encoded as an offset by `C` instead of by
+/// Alice's own public key `A`, so a malicious Bob can no longer steer the
+/// relationship between `k0` and `k1` through his choice of `B`.
+fn generator_c() -> RistrettoPoint {
+    RistrettoPoint::hash_from_bytes::<Sha512>(b"OT Generator C")
 }
 
 impl OTSender {
@@ -50,7 +102,7 @@ impl OTSender {
         let private_key = Scalar::from_bytes_mod_order_wide(&scalar_bytes);
         
         // Alice computes her pub key -> A = a * G
-        let public_key = &private_key * &RISTRETTO_BASEPOINT_POINT;
+        let public_key = private_key * RISTRETTO_BASEPOINT_POINT;
         
         let sender = OTSender {
             private_key,
@@ -68,29 +120,36 @@ impl OTSender {
     pub fn send_encrypted(&self, bob_message: &BobMessage, m0: &[u8], m1: &[u8]) -> AliceMessage2 {
         // now Alice computes two shared secrets:
         // k0 = a * B (this will match Bobs key if he chose 0)
-        // k1 = a * (B - A) (this will match Bobs key if he chose 1)
-        
+        // k1 = a * (B - C) (this will match Bobs key if he chose 1)
+        // C is a second generator independent of A, so the B Bob picks can't
+        // steer a predictable relationship between k0 and k1 through A.
+
         let k0_point = self.private_key * bob_message.public_key;
-        let k1_point = self.private_key * (bob_message.public_key - self.public_key);
-        
-        //* */ Hash the points to get symmetric keys
+        let k1_point = self.private_key * (bob_message.public_key - generator_c());
+
+        // Derive AEAD keys via HKDF, bound to both parties' public keys and a
+        // domain-separation label so k0/k1 can't be confused with one another
+        // or with keys from an unrelated session.
+        let k0 = derive_key(&k0_point, &self.public_key, &bob_message.public_key, b"oblivious-transfer k0");
+        let k1 = derive_key(&k1_point, &self.public_key, &bob_message.public_key, b"oblivious-transfer k1");
+
+        let (encrypted_m0, nonce0) = aead_encrypt(&k0, m0);
+        let (encrypted_m1, nonce1) = aead_encrypt(&k1, m1);
 
-        let k0 = hash_point(&k0_point);
-        let k1 = hash_point(&k1_point);
-        
-        let encrypted_m0 = xor_encrypt(m0, &k0);
-        let encrypted_m1 = xor_encrypt(m1, &k1);
-        
         AliceMessage2 {
             encrypted_m0,
+            nonce0,
             encrypted_m1,
+            nonce1,
         }
     }
 }
 
 impl OTReceiver {
-    // Bob initializes with his choice bit (which message he wants)
-    pub fn new(choice: bool, alice_msg: &AliceMessage1) -> (Self, BobMessage) {
+    // Bob initializes with his choice bit (which message he wants). Alice's
+    // message is still taken here to preserve the three-step message order,
+    // even though Bob's public key no longer depends on it (see generator_c).
+    pub fn new(choice: bool, _alice_msg: &AliceMessage1) -> (Self, BobMessage) {
         let mut rng = OsRng;
         
         // random priv key
@@ -100,12 +159,12 @@ impl OTReceiver {
         
         // Bob computes his pub key based on his choice:
         // - If choice = 0: B = b * G
-        // - If choice = 1: B = b * G + A
-        let mut public_key = &private_key * &RISTRETTO_BASEPOINT_POINT;
-        
+        // - If choice = 1: B = b * G + C
+        let mut public_key = private_key * RISTRETTO_BASEPOINT_POINT;
+
         if choice {
-            // Add Alices pub key if choosing message 1
-            public_key += alice_msg.public_key;
+            // Offset by the independent second generator if choosing message 1
+            public_key += generator_c();
         }
         
         let receiver = OTReceiver {
@@ -122,54 +181,92 @@ impl OTReceiver {
     }
     
     // Bob decrypts the message he chose
-    pub fn receive(&self, alice_msg2: &AliceMessage2, alice_msg1: &AliceMessage1) -> Vec<u8> {
+    pub fn receive(&self, alice_msg2: &AliceMessage2, alice_msg1: &AliceMessage1) -> Result<Vec<u8>, OtError> {
         // first Bob computes shared secret: k = b * A
         let k_point = self.private_key * alice_msg1.public_key;
-        
-        // Hash to get symmetric key
-        let k = hash_point(&k_point);
-        
-        // Decrypt the chosen message
-        if self.choice {
-            // Chose msg 1
-            xor_decrypt(&alice_msg2.encrypted_m1, &k)
-        } else {
-            // Chose msg 0
-            xor_decrypt(&alice_msg2.encrypted_m0, &k)
+
+        // Derive both candidate keys and select the one Bob is entitled to
+        // without ever branching on `self.choice` - the selection happens
+        // through constant-time conditional moves instead of an `if`, so
+        // the chosen index can't leak through timing or memory-access
+        // patterns.
+        let k0 = derive_key(&k_point, &alice_msg1.public_key, &self.public_key, b"oblivious-transfer k0");
+        let k1 = derive_key(&k_point, &alice_msg1.public_key, &self.public_key, b"oblivious-transfer k1");
+
+        let choice = Choice::from(self.choice as u8);
+        let mut key = [0u8; 32];
+        for (byte, (b0, b1)) in key.iter_mut().zip(k0.iter().zip(k1.iter())) {
+            *byte = u8::conditional_select(b0, b1, choice);
         }
+
+        let mut nonce = [0u8; 12];
+        for (byte, (b0, b1)) in nonce.iter_mut().zip(alice_msg2.nonce0.iter().zip(alice_msg2.nonce1.iter())) {
+            *byte = u8::conditional_select(b0, b1, choice);
+        }
+
+        let ciphertext = conditional_select_bytes(&alice_msg2.encrypted_m0, &alice_msg2.encrypted_m1, choice);
+
+        aead_decrypt(&key, &ciphertext, &nonce)
     }
 }
 
-// Hash a curve point to get a symmetric key
-fn hash_point(point: &RistrettoPoint) -> Vec<u8> {
-    let mut hasher = Sha256::new();
-    hasher.update(point.compress().as_bytes());
-    hasher.finalize().to_vec()
+// Select between two byte strings of possibly-different length in constant
+// time. Every byte up to the longer string's length is conditionally moved;
+// the true/false lengths differ but both are already known to an observer
+// holding both ciphertexts, so only the *content* selection needs to be
+// branch-free.
+fn conditional_select_bytes(a: &[u8], b: &[u8], choice: Choice) -> Vec<u8> {
+    let max_len = a.len().max(b.len());
+    let mut out = Vec::with_capacity(max_len);
+    for i in 0..max_len {
+        let ai = a.get(i).copied().unwrap_or(0);
+        let bi = b.get(i).copied().unwrap_or(0);
+        out.push(u8::conditional_select(&ai, &bi, choice));
+    }
+    let len = u64::conditional_select(&(a.len() as u64), &(b.len() as u64), choice);
+    out.truncate(len as usize);
+    out
 }
 
-// XOR-based encryption (stream cipher using key as pad)
-fn xor_encrypt(plaintext: &[u8], key: &[u8]) -> Vec<u8> {
-    // we'll create a keystream by repeatedly hashing - simple but not secure for real use
-    let mut keystream = Vec::new();
-    let mut current_key = key.to_vec();
-    
-    while keystream.len() < plaintext.len() {
-        let mut hasher = Sha256::new();
-        hasher.update(&current_key);
-        let hash = hasher.finalize();
-        keystream.extend_from_slice(&hash);
-        current_key = hash.to_vec();
-    }
-    
-    plaintext.iter()
-        .zip(keystream.iter())
-        .map(|(p, k)| p ^ k)
-        .collect()
+// Derive a 32-byte AEAD key from a DH shared point via HKDF-SHA256, salted
+// with both parties' public keys and domain-separated by the protocol
+// version plus `label` so k0/k1 (and any other derivation, present or
+// future) can never collide or be replayed across a transcript.
+fn derive_key(shared_point: &RistrettoPoint, pk_a: &RistrettoPoint, pk_b: &RistrettoPoint, label: &[u8]) -> [u8; 32] {
+    let mut salt = Vec::with_capacity(64);
+    salt.extend_from_slice(pk_a.compress().as_bytes());
+    salt.extend_from_slice(pk_b.compress().as_bytes());
+
+    let mut info = Vec::with_capacity(PROTOCOL_VERSION.len() + label.len());
+    info.extend_from_slice(PROTOCOL_VERSION);
+    info.extend_from_slice(label);
+
+    let hk = Hkdf::<Sha256>::new(Some(&salt), shared_point.compress().as_bytes());
+    let mut key = [0u8; 32];
+    hk.expand(&info, &mut key).expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+// Encrypt with ChaCha20-Poly1305 under a fresh random nonce.
+pub(crate) fn aead_encrypt(key: &[u8; 32], plaintext: &[u8]) -> (Vec<u8>, [u8; 12]) {
+    let cipher = ChaCha20Poly1305::new(key.into());
+
+    let mut nonce_bytes = [0u8; 12];
+    OsRng.fill_bytes(&mut nonce_bytes);
+    let nonce: Nonce = nonce_bytes.into();
+
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .expect("ChaCha20Poly1305 encryption is infallible for valid inputs");
+
+    (ciphertext, nonce_bytes)
 }
 
-// XOR-based decryption (same as encryption for XOR)
-fn xor_decrypt(ciphertext: &[u8], key: &[u8]) -> Vec<u8> {
-    xor_encrypt(ciphertext, key)
+// Decrypt with ChaCha20-Poly1305, surfacing a failed auth tag as an error.
+pub(crate) fn aead_decrypt(key: &[u8; 32], ciphertext: &[u8], nonce_bytes: &[u8; 12]) -> Result<Vec<u8>, OtError> {
+    let cipher = ChaCha20Poly1305::new(key.into());
+    let nonce: Nonce = (*nonce_bytes).into();
+    cipher.decrypt(&nonce, ciphertext).map_err(|_| OtError::Decryption)
 }
 
 #[cfg(test)]
@@ -192,8 +289,8 @@ mod tests {
         let alice_msg2 = alice.send_encrypted(&bob_msg, message0, message1);
         
         // Step 4: Bob decrypts his chosen message
-        let received = bob.receive(&alice_msg2, &alice_msg1);
-        
+        let received = bob.receive(&alice_msg2, &alice_msg1).unwrap();
+
         // Verify Bob got message 0
         assert_eq!(received, message0);
         println!("Bob chose message 0 and received: {}", String::from_utf8_lossy(&received));
@@ -210,8 +307,8 @@ mod tests {
         let (bob, bob_msg) = OTReceiver::new(true, &alice_msg1);
         
         let alice_msg2 = alice.send_encrypted(&bob_msg, message0, message1);
-        let received = bob.receive(&alice_msg2, &alice_msg1);
-        
+        let received = bob.receive(&alice_msg2, &alice_msg1).unwrap();
+
         // Verify Bob got message 1
         assert_eq!(received, message1);
         println!("Bob chose message 1 and received: {}", String::from_utf8_lossy(&received));
@@ -219,16 +316,12 @@ mod tests {
 
     #[test]
     fn test_alice_learns_nothing() {
-        
-        let message0 = b"Message 0";
-        let message1 = b"Message 1";
-        
-        let (alice, alice_msg1) = OTSender::new();
-        let (bob, bob_msg) = OTReceiver::new(true, &alice_msg1);
+        let (_alice, alice_msg1) = OTSender::new();
+        let (_bob, bob_msg) = OTReceiver::new(true, &alice_msg1);
         
         // All Alice sees is bob_msg.public_key
         // Without knowing Bob's private key, she cannot determine if:
-        // B = b*G (choice 0) or B = b*G + A (choice 1)
+        // B = b*G (choice 0) or B = b*G + C (choice 1)
         // This is the Decisional Diffie-Hellman assumption
         
         println!("Alice only sees Bob's public key: {:?}", bob_msg.public_key.compress());
@@ -250,8 +343,25 @@ mod tests {
         
         // Bob can only compute one key (k = b * A)
         // He cannot compute the other key without knowing Alice's private key 'a'
-        let received = bob.receive(&alice_msg2, &alice_msg1);
+        let received = bob.receive(&alice_msg2, &alice_msg1).unwrap();
         assert_eq!(received, message0);
         println!("Bob can only decrypt his chosen message");
     }
+
+    #[test]
+    fn test_tampered_ciphertext_is_rejected() {
+        let message0 = b"Message 0";
+        let message1 = b"Message 1";
+
+        let (alice, alice_msg1) = OTSender::new();
+        let (bob, bob_msg) = OTReceiver::new(false, &alice_msg1);
+        let mut alice_msg2 = alice.send_encrypted(&bob_msg, message0, message1);
+
+        // Flip a byte in the ciphertext Bob is about to decrypt.
+        let last = alice_msg2.encrypted_m0.len() - 1;
+        alice_msg2.encrypted_m0[last] ^= 0xFF;
+
+        let result = bob.receive(&alice_msg2, &alice_msg1);
+        assert_eq!(result, Err(OtError::Decryption));
+    }
 }
\ No newline at end of file
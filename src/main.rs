@@ -43,7 +43,7 @@ fn main() {
     //* */ Step 4: Bob decrypts only his chosen record
 
     println!("Step 4: Bob decrypts his chosen record");
-    let received = bob.receive(&alice_msg2, &alice_msg1);
+    let received = bob.receive(&alice_msg2, &alice_msg1).expect("decryption should succeed for an honest transcript");
     println!("  Bob can only decrypt Record {}\n", if bob_choice { 1 } else { 0 });
     
     println!("--- Result ---\n");
@@ -85,7 +85,7 @@ fn example_private_database_lookup() {
     let (server, server_msg1) = OTSender::new();
     let (client, client_msg) = OTReceiver::new(true, &server_msg1); // Choose Bob (index 1)
     let server_msg2 = server.send_encrypted(&client_msg, contact_alice, contact_bob);
-    let result = client.receive(&server_msg2, &server_msg1);
+    let result = client.receive(&server_msg2, &server_msg1).expect("decryption should succeed for an honest transcript");
     
     println!("Client retrieved: {}", String::from_utf8_lossy(&result));
     println!("SUCCESS: Server has no idea which contact was accessed!\n");
@@ -109,7 +109,7 @@ fn example_secure_auction() {
     // Assume Bob bid higher (choice = 1)
     let (bidder, bidder_msg) = OTReceiver::new(true, &auctioneer_msg1);
     let auctioneer_msg2 = auctioneer.send_encrypted(&bidder_msg, bid_alice_wins, bid_bob_wins);
-    let result = bidder.receive(&auctioneer_msg2, &auctioneer_msg1);
+    let result = bidder.receive(&auctioneer_msg2, &auctioneer_msg1).expect("decryption should succeed for an honest transcript");
     
     println!("Result: {}", String::from_utf8_lossy(&result));
     println!("SUCCESS: Bidders learn winner without revealing exact bids!\n");
@@ -0,0 +1,272 @@
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+use crate::{aead_decrypt, aead_encrypt, AliceMessage1, AliceMessage2, BobMessage, OTReceiver, OTSender, OtError, SealedMessage};
+
+/// Number of base OTs used to seed the extension (security parameter).
+pub const K: usize = 128;
+const K_BYTES: usize = K / 8;
+
+fn bytes_len(num_bits: usize) -> usize {
+    num_bits.div_ceil(8)
+}
+
+fn get_bit(bytes: &[u8], idx: usize) -> bool {
+    (bytes[idx / 8] >> (idx % 8)) & 1 == 1
+}
+
+fn set_bit(bytes: &mut [u8], idx: usize, value: bool) {
+    if value {
+        bytes[idx / 8] |= 1 << (idx % 8);
+    }
+}
+
+fn random_bytes(len: usize) -> Vec<u8> {
+    let mut buf = vec![0u8; len];
+    OsRng.fill_bytes(&mut buf);
+    buf
+}
+
+fn xor_bytes(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b.iter()).map(|(x, y)| x ^ y).collect()
+}
+
+// Pick out transfer j's bit from each of the K base-OT columns and pack
+// them into a single K-bit row - this is the "transpose" step of IKNP.
+fn transpose_row(columns: &[Vec<u8>], j: usize) -> Vec<u8> {
+    let mut row = vec![0u8; K_BYTES];
+    for (i, column) in columns.iter().enumerate() {
+        if get_bit(column, j) {
+            set_bit(&mut row, i, true);
+        }
+    }
+    row
+}
+
+// Key derivation for transfer j: H(j, row), domain-separated by the transfer index
+// so the same row bits never key two different transfers.
+fn extension_hash(j: usize, row: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(j.to_le_bytes());
+    hasher.update(row);
+    hasher.finalize().into()
+}
+
+/// IKNP OT extension, receiver side. Holds the real `num`-bit choice vector
+/// `r` and plays the base-OT *sender* role for each of the `K` base OTs,
+/// handing out `(t_i, t_i XOR r)` so the extension sender can only ever
+/// recover one side per column.
+pub struct OTExtensionReceiver {
+    num: usize,
+    r: Vec<u8>,
+    t_columns: Vec<Vec<u8>>,
+    base_senders: Vec<OTSender>,
+}
+
+impl OTExtensionReceiver {
+    /// Start the extension for `choices` (one bit per transfer), generating
+    /// the `K` random `t_i` columns and the base-OT senders used to deliver them.
+    pub fn extend(choices: &[bool]) -> (Self, Vec<AliceMessage1>) {
+        let num = choices.len();
+        let r_len = bytes_len(num);
+
+        let mut r = vec![0u8; r_len];
+        for (j, &bit) in choices.iter().enumerate() {
+            set_bit(&mut r, j, bit);
+        }
+
+        let mut t_columns = Vec::with_capacity(K);
+        let mut base_senders = Vec::with_capacity(K);
+        let mut base_ot_msgs = Vec::with_capacity(K);
+
+        for _ in 0..K {
+            let (sender, msg1) = OTSender::new();
+            t_columns.push(random_bytes(r_len));
+            base_senders.push(sender);
+            base_ot_msgs.push(msg1);
+        }
+
+        (
+            OTExtensionReceiver { num, r, t_columns, base_senders },
+            base_ot_msgs,
+        )
+    }
+
+    /// Respond to the extension sender's base-OT choices with `(t_i, t_i XOR r)`
+    /// for every column.
+    pub fn send_base_ot(&self, bob_messages: &[BobMessage]) -> Vec<AliceMessage2> {
+        assert_eq!(
+            bob_messages.len(),
+            K,
+            "OT extension requires exactly K={} base OTs, got {}",
+            K,
+            bob_messages.len()
+        );
+
+        self.base_senders
+            .iter()
+            .zip(self.t_columns.iter())
+            .zip(bob_messages.iter())
+            .map(|((sender, t_i), bob_msg)| {
+                let t_i_xor_r = xor_bytes(t_i, &self.r);
+                sender.send_encrypted(bob_msg, t_i, &t_i_xor_r)
+            })
+            .collect()
+    }
+
+    /// Decrypt the ciphertext the extension sender produced for transfer `j`.
+    /// Returns `Err` instead of garbage if the auth tag doesn't check out.
+    pub fn decrypt(&self, j: usize, ciphertext: &[u8], nonce: &[u8; 12]) -> Result<Vec<u8>, OtError> {
+        assert!(j < self.num, "transfer index {} out of range (num={})", j, self.num);
+        let row = transpose_row(&self.t_columns, j);
+        aead_decrypt(&extension_hash(j, &row), ciphertext, nonce)
+    }
+
+    pub fn num_transfers(&self) -> usize {
+        self.num
+    }
+}
+
+/// IKNP OT extension, sender side, mid-setup. Plays the base-OT *receiver*
+/// role with a random K-bit choice string `s`.
+pub struct OTExtensionSender {
+    num: usize,
+    s_packed: Vec<u8>,
+    base_receivers: Vec<OTReceiver>,
+}
+
+impl OTExtensionSender {
+    /// Respond to the `K` base-OT offers from the extension receiver by picking
+    /// a random K-bit string `s` and choosing accordingly.
+    pub fn extend(base_ot_msgs: &[AliceMessage1], num: usize) -> (Self, Vec<BobMessage>) {
+        assert_eq!(
+            base_ot_msgs.len(),
+            K,
+            "OT extension requires exactly K={} base OTs, got {}",
+            K,
+            base_ot_msgs.len()
+        );
+
+        let mut rng = OsRng;
+        let mut s_packed = vec![0u8; K_BYTES];
+        let mut base_receivers = Vec::with_capacity(K);
+        let mut bob_msgs = Vec::with_capacity(K);
+
+        for (i, msg1) in base_ot_msgs.iter().enumerate() {
+            let bit = rng.next_u32() & 1 == 1;
+            set_bit(&mut s_packed, i, bit);
+            let (receiver, bob_msg) = OTReceiver::new(bit, msg1);
+            base_receivers.push(receiver);
+            bob_msgs.push(bob_msg);
+        }
+
+        (OTExtensionSender { num, s_packed, base_receivers }, bob_msgs)
+    }
+
+    /// Complete the base OTs and derive the `q` column matrix, yielding a
+    /// streaming handle that can encrypt any of the `num` transfers on demand.
+    /// Fails if any base-OT reply doesn't authenticate - e.g. a malicious
+    /// extension receiver tampering with a base-OT transcript.
+    pub fn finish(self, base_ot_msgs: &[AliceMessage1], base_ot_replies: &[AliceMessage2]) -> Result<OTExtensionKeys, OtError> {
+        assert_eq!(
+            base_ot_replies.len(),
+            K,
+            "OT extension requires exactly K={} base OT replies, got {}",
+            K,
+            base_ot_replies.len()
+        );
+
+        let q_columns = self
+            .base_receivers
+            .iter()
+            .zip(base_ot_msgs.iter())
+            .zip(base_ot_replies.iter())
+            .map(|((receiver, msg1), msg2)| receiver.receive(msg2, msg1))
+            .collect::<Result<Vec<_>, OtError>>()?;
+
+        Ok(OTExtensionKeys { num: self.num, s_packed: self.s_packed, q_columns })
+    }
+}
+
+/// Streaming per-transfer encrypt handle produced once the base OTs are done.
+/// Encrypting transfer `j` costs one transpose pass plus two hashes - no
+/// curve operations - so this is cheap enough to call millions of times.
+pub struct OTExtensionKeys {
+    num: usize,
+    s_packed: Vec<u8>,
+    q_columns: Vec<Vec<u8>>,
+}
+
+impl OTExtensionKeys {
+    /// Encrypt `m0`/`m1` for transfer `j` under its two derived keys with
+    /// ChaCha20-Poly1305, each under its own fresh nonce.
+    pub fn encrypt(&self, j: usize, m0: &[u8], m1: &[u8]) -> (SealedMessage, SealedMessage) {
+        assert!(j < self.num, "transfer index {} out of range (num={})", j, self.num);
+
+        let row = transpose_row(&self.q_columns, j);
+        let key0 = extension_hash(j, &row);
+        let row_xor_s = xor_bytes(&row, &self.s_packed);
+        let key1 = extension_hash(j, &row_xor_s);
+
+        (aead_encrypt(&key0, m0), aead_encrypt(&key1, m1))
+    }
+
+    pub fn num_transfers(&self) -> usize {
+        self.num
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_extension(choices: &[bool]) -> (OTExtensionReceiver, OTExtensionKeys) {
+        let (receiver, base_ot_msgs) = OTExtensionReceiver::extend(choices);
+        let (sender, bob_msgs) = OTExtensionSender::extend(&base_ot_msgs, choices.len());
+        let base_ot_replies = receiver.send_base_ot(&bob_msgs);
+        let keys = sender.finish(&base_ot_msgs, &base_ot_replies).unwrap();
+        (receiver, keys)
+    }
+
+    #[test]
+    fn test_large_batch_of_cheap_ots() {
+        let num = 10_000;
+        let choices: Vec<bool> = (0..num).map(|i| i % 3 == 0).collect();
+        let (receiver, keys) = run_extension(&choices);
+
+        for (j, &choice) in choices.iter().enumerate() {
+            let m0 = format!("message {j} - zero").into_bytes();
+            let m1 = format!("message {j} - one").into_bytes();
+            let (c0, c1) = keys.encrypt(j, &m0, &m1);
+            let (chosen_ct, chosen_nonce) = if choice { &c1 } else { &c0 };
+            let received = receiver.decrypt(j, chosen_ct, chosen_nonce).unwrap();
+            let expected = if choice { &m1 } else { &m0 };
+            assert_eq!(&received, expected);
+        }
+    }
+
+    #[test]
+    fn test_receiver_cannot_decrypt_unchosen_message() {
+        let choices = vec![false, true, false, true];
+        let (receiver, keys) = run_extension(&choices);
+
+        let ((c0, nonce0), _c1) = keys.encrypt(0, b"zero payload", b"one payload");
+        // choices[0] is false, so decrypting the "one" ciphertext must not
+        // authenticate: Bob derived the key for "zero", not "one".
+        let (c1, nonce1) = keys.encrypt(0, b"zero payload", b"one payload").1;
+        assert!(receiver.decrypt(0, &c1, &nonce1).is_err());
+        let correct = receiver.decrypt(0, &c0, &nonce0).unwrap();
+        assert_eq!(correct, b"zero payload".to_vec());
+    }
+
+    #[test]
+    #[should_panic(expected = "OT extension requires exactly K")]
+    fn test_mismatched_base_ot_count_panics() {
+        let choices = vec![true, false, true];
+        let (_receiver, base_ot_msgs) = OTExtensionReceiver::extend(&choices);
+        // Drop one base OT message to simulate a mismatched transcript.
+        let truncated = &base_ot_msgs[..K - 1];
+        let _ = OTExtensionSender::extend(truncated, choices.len());
+    }
+}
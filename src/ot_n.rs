@@ -0,0 +1,217 @@
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+use crate::{aead_decrypt, aead_encrypt, AliceMessage1, AliceMessage2, BobMessage, OTReceiver, OTSender, OtError, SealedMessage};
+
+const SHARE_LEN: usize = 32;
+
+fn random_bytes(len: usize) -> Vec<u8> {
+    let mut buf = vec![0u8; len];
+    OsRng.fill_bytes(&mut buf);
+    buf
+}
+
+fn xor_bytes(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b.iter()).map(|(x, y)| x ^ y).collect()
+}
+
+// Number of base 1-of-2 OTs needed to address N leaves: ceil(log2(N)).
+fn levels_for(n: usize) -> usize {
+    assert!(n >= 2, "OT-N requires at least 2 messages, got {}", n);
+    let mut levels = 0;
+    while (1usize << levels) < n {
+        levels += 1;
+    }
+    levels
+}
+
+// Domain-separated SHA-256 hash binding a leaf key to its index, taken over
+// the combined level shares (plain SHA-256, not the HKDF of the base OT's
+// derive_key, and with no public keys mixed in).
+fn leaf_hash(index: usize, combined: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"OT-N leaf");
+    hasher.update(index.to_le_bytes());
+    hasher.update(combined);
+    hasher.finalize().into()
+}
+
+// XOR together the per-level share selected by each bit of `leaf`'s binary
+// expansion. The result is not hashed here; callers pass it to `leaf_hash`
+// to get that leaf's key.
+fn leaf_key(leaf: usize, levels: usize, level_shares: &[(Vec<u8>, Vec<u8>)]) -> Vec<u8> {
+    let mut combined = vec![0u8; SHARE_LEN];
+    for (l, (share0, share1)) in level_shares.iter().enumerate().take(levels) {
+        let bit = (leaf >> l) & 1 == 1;
+        let share = if bit { share1 } else { share0 };
+        combined = xor_bytes(&combined, share);
+    }
+    combined
+}
+
+/// 1-out-of-N OT, sender side (Naor-Pinkas tree construction). Holds the `N`
+/// messages and runs `ceil(log2 N)` base 1-of-2 OTs, one per bit of the
+/// receiver's index, so the leaf key for message `j` is the hash of `j`
+/// together with the XOR of the level shares picked by `j`'s binary expansion.
+pub struct OTSenderN {
+    level_senders: Vec<OTSender>,
+    level_shares: Vec<(Vec<u8>, Vec<u8>)>,
+    ciphertexts: Vec<SealedMessage>,
+}
+
+impl OTSenderN {
+    /// Alice generates one base OT per tree level and encrypts all `N` messages
+    /// under their leaf keys up front - the leaf keys don't depend on the
+    /// receiver at all, only on Alice's own random level shares.
+    pub fn new(messages: &[&[u8]]) -> (Self, Vec<AliceMessage1>) {
+        let levels = levels_for(messages.len());
+
+        let mut level_senders = Vec::with_capacity(levels);
+        let mut level_shares = Vec::with_capacity(levels);
+        let mut alice_msgs = Vec::with_capacity(levels);
+
+        for _ in 0..levels {
+            let (sender, msg1) = OTSender::new();
+            level_senders.push(sender);
+            level_shares.push((random_bytes(SHARE_LEN), random_bytes(SHARE_LEN)));
+            alice_msgs.push(msg1);
+        }
+
+        let ciphertexts = messages
+            .iter()
+            .enumerate()
+            .map(|(j, message)| {
+                let key = leaf_hash(j, &leaf_key(j, levels, &level_shares));
+                aead_encrypt(&key, message)
+            })
+            .collect();
+
+        (OTSenderN { level_senders, level_shares, ciphertexts }, alice_msgs)
+    }
+
+    /// Encrypt each level's two key shares under that level's base OT.
+    pub fn send_base_ots(&self, bob_messages: &[BobMessage]) -> Vec<AliceMessage2> {
+        assert_eq!(
+            bob_messages.len(),
+            self.level_senders.len(),
+            "expected {} base OTs, got {}",
+            self.level_senders.len(),
+            bob_messages.len()
+        );
+
+        self.level_senders
+            .iter()
+            .zip(self.level_shares.iter())
+            .zip(bob_messages.iter())
+            .map(|((sender, (share0, share1)), bob_msg)| sender.send_encrypted(bob_msg, share0, share1))
+            .collect()
+    }
+
+    /// The `N` leaf ciphertexts (with their per-message nonces), to be
+    /// broadcast alongside the base-OT replies.
+    pub fn ciphertexts(&self) -> &[SealedMessage] {
+        &self.ciphertexts
+    }
+}
+
+/// 1-out-of-N OT, receiver side. Picks an index `i` and commits one choice
+/// bit per tree level - the bits of `i`'s binary expansion - so it only ever
+/// learns the level shares that combine into leaf `i`'s key.
+pub struct OTReceiverN {
+    index: usize,
+    levels: usize,
+    level_receivers: Vec<OTReceiver>,
+}
+
+impl OTReceiverN {
+    /// Bob commits to index `i` (out of `num_messages`) across the `levels`
+    /// base OTs Alice already started.
+    pub fn new(index: usize, num_messages: usize, alice_msgs: &[AliceMessage1]) -> (Self, Vec<BobMessage>) {
+        assert!(index < num_messages, "index {} out of range for N={}", index, num_messages);
+
+        let levels = levels_for(num_messages);
+        assert_eq!(alice_msgs.len(), levels, "expected {} base OTs, got {}", levels, alice_msgs.len());
+
+        let mut level_receivers = Vec::with_capacity(levels);
+        let mut bob_msgs = Vec::with_capacity(levels);
+
+        for (l, msg1) in alice_msgs.iter().enumerate() {
+            let bit = (index >> l) & 1 == 1;
+            let (receiver, bob_msg) = OTReceiver::new(bit, msg1);
+            level_receivers.push(receiver);
+            bob_msgs.push(bob_msg);
+        }
+
+        (OTReceiverN { index, levels, level_receivers }, bob_msgs)
+    }
+
+    /// Recover the level shares Bob is entitled to, combine them into the
+    /// leaf key for `index`, and decrypt message `index`. Any bad base-OT
+    /// reply or a forged leaf ciphertext comes back as `Err`, not a wrong answer.
+    pub fn receive(
+        &self,
+        alice_msgs: &[AliceMessage1],
+        alice_msg2s: &[AliceMessage2],
+        ciphertexts: &[SealedMessage],
+    ) -> Result<Vec<u8>, OtError> {
+        let mut combined = vec![0u8; SHARE_LEN];
+        for l in 0..self.levels {
+            let share = self.level_receivers[l].receive(&alice_msg2s[l], &alice_msgs[l])?;
+            combined = xor_bytes(&combined, &share);
+        }
+        let key = leaf_hash(self.index, &combined);
+        let (ciphertext, nonce) = &ciphertexts[self.index];
+        aead_decrypt(&key, ciphertext, nonce)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_ot_n(messages: &[&[u8]], index: usize) -> Vec<u8> {
+        let (sender, alice_msgs) = OTSenderN::new(messages);
+        let (receiver, bob_msgs) = OTReceiverN::new(index, messages.len(), &alice_msgs);
+        let alice_msg2s = sender.send_base_ots(&bob_msgs);
+        receiver.receive(&alice_msgs, &alice_msg2s, sender.ciphertexts()).unwrap()
+    }
+
+    #[test]
+    fn test_receiver_gets_chosen_message_non_power_of_two() {
+        let messages: Vec<&[u8]> = vec![b"zero", b"one", b"two", b"three", b"four"];
+
+        for (i, expected) in messages.iter().enumerate() {
+            let received = run_ot_n(&messages, i);
+            assert_eq!(&received, expected);
+        }
+    }
+
+    #[test]
+    fn test_receiver_cannot_decrypt_unchosen_message() {
+        let messages: Vec<&[u8]> = vec![b"alpha", b"beta", b"gamma", b"delta"];
+        let (sender, alice_msgs) = OTSenderN::new(&messages);
+        let (receiver, bob_msgs) = OTReceiverN::new(1, messages.len(), &alice_msgs);
+        let alice_msg2s = sender.send_base_ots(&bob_msgs);
+
+        // Bob chose index 1; decrypting index 2's ciphertext with his recovered
+        // key must not authenticate - he derived the key for leaf 1, not leaf 2.
+        let mut combined = vec![0u8; SHARE_LEN];
+        for l in 0..receiver.levels {
+            let share = receiver.level_receivers[l].receive(&alice_msg2s[l], &alice_msgs[l]).unwrap();
+            combined = xor_bytes(&combined, &share);
+        }
+        let key = leaf_hash(receiver.index, &combined);
+        let (ciphertext, nonce) = &sender.ciphertexts()[2];
+        assert!(aead_decrypt(&key, ciphertext, nonce).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "expected")]
+    fn test_mismatched_base_ot_count_panics() {
+        let messages: Vec<&[u8]> = vec![b"a", b"b", b"c"];
+        let (_sender, alice_msgs) = OTSenderN::new(&messages);
+        let truncated = &alice_msgs[..alice_msgs.len() - 1];
+        let _ = OTReceiverN::new(0, messages.len(), truncated);
+    }
+}
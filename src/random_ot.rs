@@ -0,0 +1,313 @@
+use std::collections::VecDeque;
+
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+use crate::{aead_decrypt, aead_encrypt, AliceMessage1, AliceMessage2, BobMessage, OTReceiver, OTSender, OtError, SealedMessage};
+
+const PAD_LEN: usize = 32;
+
+fn random_bytes(len: usize) -> Vec<u8> {
+    let mut buf = vec![0u8; len];
+    OsRng.fill_bytes(&mut buf);
+    buf
+}
+
+fn xor_bytes(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b.iter()).map(|(x, y)| x ^ y).collect()
+}
+
+// Pads are always PAD_LEN (32) bytes, so they double directly as AEAD keys.
+fn pad_key(pad: &[u8]) -> [u8; 32] {
+    pad.try_into().expect("pad is always PAD_LEN bytes")
+}
+
+/// Random-OT precomputation, sender side. Runs `num` base OTs on random pads
+/// instead of real payloads, so all the expensive curve work happens ahead of
+/// time, independent of whatever messages get transferred later.
+pub struct RandomOTSender {
+    base_senders: Vec<OTSender>,
+    pads: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl RandomOTSender {
+    /// Generate `num` random pad pairs `(r0, r1)` and start one base OT per pair.
+    pub fn precompute(num: usize) -> (Self, Vec<AliceMessage1>) {
+        let mut base_senders = Vec::with_capacity(num);
+        let mut pads = Vec::with_capacity(num);
+        let mut alice_msgs = Vec::with_capacity(num);
+
+        for _ in 0..num {
+            let (sender, msg1) = OTSender::new();
+            base_senders.push(sender);
+            pads.push((random_bytes(PAD_LEN), random_bytes(PAD_LEN)));
+            alice_msgs.push(msg1);
+        }
+
+        (RandomOTSender { base_senders, pads }, alice_msgs)
+    }
+
+    /// Finish the curve step by delivering each pad pair through its base OT,
+    /// then hand back a pool the online phase can draw down as transfers occur.
+    pub fn finish(self, bob_messages: &[BobMessage]) -> (RandomOtSenderPool, Vec<AliceMessage2>) {
+        assert_eq!(
+            bob_messages.len(),
+            self.base_senders.len(),
+            "expected {} base OTs, got {}",
+            self.base_senders.len(),
+            bob_messages.len()
+        );
+
+        let replies = self
+            .base_senders
+            .iter()
+            .zip(self.pads.iter())
+            .zip(bob_messages.iter())
+            .map(|((sender, (r0, r1)), bob_msg)| sender.send_encrypted(bob_msg, r0, r1))
+            .collect();
+
+        (RandomOtSenderPool { pads: self.pads.into() }, replies)
+    }
+}
+
+/// A drawn-down pool of precomputed sender-side random pads.
+pub struct RandomOtSenderPool {
+    pads: VecDeque<(Vec<u8>, Vec<u8>)>,
+}
+
+impl RandomOtSenderPool {
+    /// Beaver-derandomize: consume one precomputed pad pair and AEAD-encrypt
+    /// the real messages under it. No curve operations happen here - the pads
+    /// were already the output of the base OTs run in `precompute`/`finish`.
+    pub fn derandomize(&mut self, m0: &[u8], m1: &[u8]) -> (SealedMessage, SealedMessage) {
+        let (r0, r1) = self.pads.pop_front().expect("random OT pool exhausted");
+        (aead_encrypt(&pad_key(&r0), m0), aead_encrypt(&pad_key(&r1), m1))
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.pads.len()
+    }
+}
+
+/// Random-OT precomputation, receiver side. Picks `num` random choice bits
+/// up front and runs the matching base OTs to fetch one pad per choice.
+pub struct RandomOTReceiver {
+    base_receivers: Vec<OTReceiver>,
+    choices: Vec<bool>,
+}
+
+impl RandomOTReceiver {
+    /// Commit to `num` random choice bits against the sender's base OTs.
+    /// This same shape is reused by the correlated-OT receiver: from Bob's
+    /// side, receiving one of two correlated pads looks identical to
+    /// receiving one of two independent ones.
+    pub fn precompute(num: usize, alice_msgs: &[AliceMessage1]) -> (Self, Vec<BobMessage>) {
+        assert_eq!(alice_msgs.len(), num, "expected {} base OTs, got {}", num, alice_msgs.len());
+
+        let mut rng = OsRng;
+        let mut base_receivers = Vec::with_capacity(num);
+        let mut choices = Vec::with_capacity(num);
+        let mut bob_msgs = Vec::with_capacity(num);
+
+        for msg1 in alice_msgs {
+            let bit = rng.next_u32() & 1 == 1;
+            let (receiver, bob_msg) = OTReceiver::new(bit, msg1);
+            base_receivers.push(receiver);
+            choices.push(bit);
+            bob_msgs.push(bob_msg);
+        }
+
+        (RandomOTReceiver { base_receivers, choices }, bob_msgs)
+    }
+
+    /// Complete the base OTs, yielding a pool of `(choice, pad)` pairs. A
+    /// corrupted base-OT reply surfaces here as `Err` rather than a bad pad.
+    pub fn finish(self, alice_msgs: &[AliceMessage1], alice_msg2s: &[AliceMessage2]) -> Result<RandomOtReceiverPool, OtError> {
+        assert_eq!(
+            alice_msg2s.len(),
+            self.base_receivers.len(),
+            "expected {} base OT replies, got {}",
+            self.base_receivers.len(),
+            alice_msg2s.len()
+        );
+
+        let entries = self
+            .base_receivers
+            .iter()
+            .zip(alice_msgs.iter())
+            .zip(alice_msg2s.iter())
+            .zip(self.choices.iter())
+            .map(|(((receiver, msg1), msg2), &choice)| {
+                let pad = receiver.receive(msg2, msg1)?;
+                Ok((choice, pad))
+            })
+            .collect::<Result<VecDeque<_>, OtError>>()?;
+
+        Ok(RandomOtReceiverPool { entries })
+    }
+}
+
+/// A drawn-down pool of precomputed receiver-side `(choice, pad)` pairs.
+pub struct RandomOtReceiverPool {
+    entries: VecDeque<(bool, Vec<u8>)>,
+}
+
+impl RandomOtReceiverPool {
+    /// Beaver-derandomize: consume the next `(choice, pad)` and recover the
+    /// chosen message from the sender's derandomized ciphertexts. Returns
+    /// `Err` if that ciphertext does not authenticate under the pad.
+    pub fn derandomize(&mut self, c0: &SealedMessage, c1: &SealedMessage) -> Result<Vec<u8>, OtError> {
+        let (choice, pad) = self.entries.pop_front().expect("random OT pool exhausted");
+        let (ciphertext, nonce) = if choice { c1 } else { c0 };
+        aead_decrypt(&pad_key(&pad), ciphertext, nonce)
+    }
+
+    /// Draw the next raw `(choice, pad)` pair directly, for correlated-OT
+    /// consumers (e.g. GMW/garbled-circuit evaluation) that don't want the
+    /// message-derandomization step.
+    pub fn next_pad(&mut self) -> (bool, Vec<u8>) {
+        self.entries.pop_front().expect("random OT pool exhausted")
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+/// Correlated-OT precomputation, sender side. Like `RandomOTSender`, but
+/// every pad pair shares the same global correlation `delta`: pair `i` is
+/// `(r0_i, r0_i XOR delta)` rather than two independent randoms. This is the
+/// standard building block for GMW/garbled-circuit evaluation.
+pub struct CorrelatedOTSender {
+    base_senders: Vec<OTSender>,
+    delta: Vec<u8>,
+    r0s: Vec<Vec<u8>>,
+}
+
+impl CorrelatedOTSender {
+    /// Fix the correlation `delta` and generate `num` random `r0` values, one
+    /// per base OT.
+    pub fn precompute(num: usize, delta: &[u8]) -> (Self, Vec<AliceMessage1>) {
+        assert_eq!(delta.len(), PAD_LEN, "correlated-OT delta must be {} bytes, got {}", PAD_LEN, delta.len());
+
+        let mut base_senders = Vec::with_capacity(num);
+        let mut r0s = Vec::with_capacity(num);
+        let mut alice_msgs = Vec::with_capacity(num);
+
+        for _ in 0..num {
+            let (sender, msg1) = OTSender::new();
+            base_senders.push(sender);
+            r0s.push(random_bytes(PAD_LEN));
+            alice_msgs.push(msg1);
+        }
+
+        (CorrelatedOTSender { base_senders, delta: delta.to_vec(), r0s }, alice_msgs)
+    }
+
+    /// Finish the curve step by delivering `(r0_i, r0_i XOR delta)` through
+    /// each base OT.
+    pub fn finish(self, bob_messages: &[BobMessage]) -> (CorrelatedOtSenderPool, Vec<AliceMessage2>) {
+        assert_eq!(
+            bob_messages.len(),
+            self.base_senders.len(),
+            "expected {} base OTs, got {}",
+            self.base_senders.len(),
+            bob_messages.len()
+        );
+
+        let replies = self
+            .base_senders
+            .iter()
+            .zip(self.r0s.iter())
+            .zip(bob_messages.iter())
+            .map(|((sender, r0), bob_msg)| {
+                let r1 = xor_bytes(r0, &self.delta);
+                sender.send_encrypted(bob_msg, r0, &r1)
+            })
+            .collect();
+
+        (CorrelatedOtSenderPool { r0s: self.r0s.into() }, replies)
+    }
+}
+
+/// A drawn-down pool of precomputed `r0` values sharing the sender's `delta`.
+pub struct CorrelatedOtSenderPool {
+    r0s: VecDeque<Vec<u8>>,
+}
+
+impl CorrelatedOtSenderPool {
+    /// Draw the next `r0`; the matching correlated pair is `r0 XOR delta`.
+    pub fn next_pad(&mut self) -> Vec<u8> {
+        self.r0s.pop_front().expect("correlated OT pool exhausted")
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.r0s.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_precompute_then_derandomize_many_transfers() {
+        let num = 500;
+        let (sender, alice_msgs) = RandomOTSender::precompute(num);
+        let (receiver, bob_msgs) = RandomOTReceiver::precompute(num, &alice_msgs);
+        let (mut sender_pool, alice_msg2s) = sender.finish(&bob_msgs);
+        let mut receiver_pool = receiver.finish(&alice_msgs, &alice_msg2s).unwrap();
+
+        for i in 0..num {
+            let m0 = format!("zero-{i}").into_bytes();
+            let m1 = format!("one-{i}").into_bytes();
+            let (c0, c1) = sender_pool.derandomize(&m0, &m1);
+            let received = receiver_pool.derandomize(&c0, &c1).unwrap();
+            // Whichever pad Bob drew matches exactly one of the two ciphertexts.
+            assert!(received == m0 || received == m1);
+        }
+        assert_eq!(sender_pool.remaining(), 0);
+        assert_eq!(receiver_pool.remaining(), 0);
+    }
+
+    #[test]
+    fn test_correlated_ot_shares_delta() {
+        let num = 16;
+        let delta = random_bytes(PAD_LEN);
+
+        let (sender, alice_msgs) = CorrelatedOTSender::precompute(num, &delta);
+        let (receiver, bob_msgs) = RandomOTReceiver::precompute(num, &alice_msgs);
+        let (mut sender_pool, alice_msg2s) = sender.finish(&bob_msgs);
+        let mut receiver_pool = receiver.finish(&alice_msgs, &alice_msg2s).unwrap();
+
+        for _ in 0..num {
+            let r0 = sender_pool.next_pad();
+            let r1 = xor_bytes(&r0, &delta);
+            let (choice, pad) = receiver_pool.next_pad();
+            let expected = if choice { &r1 } else { &r0 };
+            assert_eq!(&pad, expected);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "correlated-OT delta must be")]
+    fn test_mismatched_delta_length_panics() {
+        let short_delta = random_bytes(PAD_LEN - 1);
+        let _ = CorrelatedOTSender::precompute(4, &short_delta);
+    }
+
+    #[test]
+    #[should_panic(expected = "pool exhausted")]
+    fn test_draining_past_capacity_panics() {
+        let num = 2;
+        let (sender, alice_msgs) = RandomOTSender::precompute(num);
+        let (_receiver, bob_msgs) = RandomOTReceiver::precompute(num, &alice_msgs);
+        let (mut sender_pool, _alice_msg2s) = sender.finish(&bob_msgs);
+
+        for _ in 0..num {
+            sender_pool.derandomize(b"m0", b"m1");
+        }
+        // One draw too many.
+        sender_pool.derandomize(b"m0", b"m1");
+    }
+}